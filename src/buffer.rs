@@ -7,16 +7,20 @@ use bytes::{Bytes, BytesMut};
 use hyper::body::{Buf, HttpBody};
 use hyper::Request;
 
+use crate::error::Error;
+
 pub(crate) struct Buffer<B> {
     req: Request<B>,
     buf: BytesMut,
+    limit: usize,
 }
 
-impl<B> From<Request<B>> for Buffer<B> {
-    fn from(req: Request<B>) -> Buffer<B> {
+impl<B> Buffer<B> {
+    pub(crate) fn new(req: Request<B>, limit: usize) -> Buffer<B> {
         Buffer {
             req,
             buf: BytesMut::new(),
+            limit,
         }
     }
 }
@@ -41,15 +45,21 @@ where
     B::Data: Send,
     B::Error: Into<Box<dyn StdError + Send + Sync>>,
 {
-    type Output = Result<Bytes, B::Error>;
+    type Output = Result<Bytes, Error>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
         let self_ref = self.get_mut();
         let body = self_ref.req.body_mut();
         loop {
             match try_ready!(HttpBody::poll_data(Pin::new(body), cx)) {
-                Some(Ok(chunk)) => self_ref.buf.extend_from_slice(chunk.chunk()),
-                Some(Err(e)) => return Poll::Ready(Err(e)),
+                Some(Ok(chunk)) => {
+                    let chunk = chunk.chunk();
+                    if self_ref.buf.len() + chunk.len() > self_ref.limit {
+                        return Poll::Ready(Err(Error::BodyTooLarge { limit: self_ref.limit }));
+                    }
+                    self_ref.buf.extend_from_slice(chunk);
+                }
+                Some(Err(e)) => return Poll::Ready(Err(Error::request(e))),
                 None => {
                     let buf = ::std::mem::replace(&mut self_ref.buf, BytesMut::new());
                     return Poll::Ready(Ok(buf.freeze()));
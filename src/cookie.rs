@@ -0,0 +1,203 @@
+
+use std::sync::{Arc, Mutex};
+
+use hyper::header::{HeaderMap, HeaderValue, SET_COOKIE};
+use hyper::Uri;
+
+/// A shared store for cookies set via `Set-Cookie` responses.
+///
+/// The store honors the `Set-Cookie` headers returned by intermediate responses
+/// and re-injects the matching `Cookie` header before following the next hop,
+/// so redirect flows that establish a session cookie (common in login and SSO
+/// flows) work as expected. `Domain`, `Path` and `Secure` attributes are
+/// respected, and cookies are only sent to hosts they were scoped to.
+///
+/// The store is cheap to clone and all clones share the same backing storage,
+/// so a single jar can be reused across requests to carry cookies between them.
+#[derive(Debug, Clone, Default)]
+pub struct CookieStore {
+    cookies: Arc<Mutex<Vec<Cookie>>>,
+}
+
+#[derive(Debug, Clone)]
+struct Cookie {
+    name: String,
+    value: String,
+    // The host the cookie was received from, used for host-only cookies.
+    host: String,
+    // The `Domain` attribute, if any, always stored without a leading dot.
+    domain: Option<String>,
+    path: String,
+    secure: bool,
+}
+
+impl CookieStore {
+    /// Create an empty cookie store.
+    pub fn new() -> CookieStore {
+        CookieStore::default()
+    }
+
+    /// Parse and store every `Set-Cookie` header from a response received from `uri`.
+    pub(crate) fn store_response(&self, uri: &Uri, headers: &HeaderMap) {
+        let host = match uri.host() {
+            Some(host) => host,
+            None => return,
+        };
+        let mut store = self.cookies.lock().unwrap();
+        for value in headers.get_all(SET_COOKIE) {
+            let raw = match value.to_str() {
+                Ok(raw) => raw,
+                Err(_) => continue,
+            };
+            if let Some(cookie) = Cookie::parse(uri, host, raw) {
+                store.retain(|c| {
+                    !(c.name == cookie.name && c.host == cookie.host && c.path == cookie.path)
+                });
+                store.push(cookie);
+            }
+        }
+    }
+
+    /// Build the `Cookie` header to send when requesting `uri`, if any cookies match.
+    pub(crate) fn cookie_header(&self, uri: &Uri) -> Option<HeaderValue> {
+        let store = self.cookies.lock().unwrap();
+        let mut pairs = Vec::new();
+        for cookie in store.iter() {
+            if cookie.matches(uri) {
+                pairs.push(format!("{}={}", cookie.name, cookie.value));
+            }
+        }
+        if pairs.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&pairs.join("; ")).ok()
+    }
+}
+
+impl Cookie {
+    fn parse(origin: &Uri, host: &str, raw: &str) -> Option<Cookie> {
+        let mut parts = raw.split(';');
+        let pair = parts.next()?;
+        let eq = pair.find('=')?;
+        let name = pair[..eq].trim().to_owned();
+        let value = pair[eq + 1..].trim().to_owned();
+        if name.is_empty() {
+            return None;
+        }
+        let mut domain = None;
+        let mut path = None;
+        let mut secure = false;
+        for attr in parts {
+            let attr = attr.trim();
+            let (key, val) = match attr.find('=') {
+                Some(i) => (&attr[..i], attr[i + 1..].trim()),
+                None => (attr, ""),
+            };
+            if key.eq_ignore_ascii_case("domain") && !val.is_empty() {
+                domain = Some(val.trim_start_matches('.').to_ascii_lowercase());
+            } else if key.eq_ignore_ascii_case("path") {
+                path = Some(val.to_owned());
+            } else if key.eq_ignore_ascii_case("secure") {
+                secure = true;
+            }
+        }
+        let path = path.filter(|p| p.starts_with('/')).unwrap_or_else(|| default_path(origin));
+        Some(Cookie {
+            name,
+            value,
+            host: host.to_ascii_lowercase(),
+            domain,
+            path,
+            secure,
+        })
+    }
+
+    fn matches(&self, uri: &Uri) -> bool {
+        if self.secure && uri.scheme_str() != Some("https") {
+            return false;
+        }
+        let host = match uri.host() {
+            Some(host) => host.to_ascii_lowercase(),
+            None => return false,
+        };
+        let host_ok = match &self.domain {
+            Some(domain) => domain_match(&host, domain),
+            None => host == self.host,
+        };
+        host_ok && path_match(uri.path(), &self.path)
+    }
+}
+
+fn default_path(uri: &Uri) -> String {
+    let path = uri.path();
+    match path.rfind('/') {
+        Some(0) | None => "/".to_owned(),
+        Some(i) => path[..i].to_owned(),
+    }
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn path_match(request: &str, cookie: &str) -> bool {
+    if request == cookie {
+        return true;
+    }
+    if !request.starts_with(cookie) {
+        return false;
+    }
+    cookie.ends_with('/') || request[cookie.len()..].starts_with('/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CookieStore, Uri};
+    use hyper::header::{HeaderMap, HeaderValue, SET_COOKIE};
+
+    fn set_cookie(uri: &str, value: &str) -> CookieStore {
+        let store = CookieStore::new();
+        let mut headers = HeaderMap::new();
+        headers.insert(SET_COOKIE, HeaderValue::from_str(value).unwrap());
+        store.store_response(&uri.parse::<Uri>().unwrap(), &headers);
+        store
+    }
+
+    fn header_for(store: &CookieStore, uri: &str) -> Option<String> {
+        store
+            .cookie_header(&uri.parse::<Uri>().unwrap())
+            .map(|v| v.to_str().unwrap().to_owned())
+    }
+
+    #[test]
+    fn replays_host_only_cookie_on_same_host() {
+        let store = set_cookie("http://example.org/login", "sid=abc");
+        assert_eq!(Some("sid=abc".to_owned()), header_for(&store, "http://example.org/home"));
+    }
+
+    #[test]
+    fn does_not_leak_to_other_host() {
+        let store = set_cookie("http://example.org/login", "sid=abc");
+        assert_eq!(None, header_for(&store, "http://evil.example.com/"));
+    }
+
+    #[test]
+    fn respects_path_scope() {
+        let store = set_cookie("http://example.org/app/login", "sid=abc; Path=/app");
+        assert_eq!(Some("sid=abc".to_owned()), header_for(&store, "http://example.org/app/home"));
+        assert_eq!(None, header_for(&store, "http://example.org/other"));
+    }
+
+    #[test]
+    fn secure_cookie_requires_https() {
+        let store = set_cookie("https://example.org/login", "sid=abc; Secure");
+        assert_eq!(None, header_for(&store, "http://example.org/home"));
+        assert_eq!(Some("sid=abc".to_owned()), header_for(&store, "https://example.org/home"));
+    }
+
+    #[test]
+    fn domain_cookie_matches_subdomain() {
+        let store = set_cookie("http://example.org/", "sid=abc; Domain=example.org");
+        assert_eq!(Some("sid=abc".to_owned()), header_for(&store, "http://api.example.org/"));
+    }
+}
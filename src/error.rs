@@ -2,6 +2,7 @@ use std::error::Error as StdError;
 use std::fmt;
 
 use hyper::http;
+use hyper::Uri;
 
 #[derive(Debug)]
 pub enum Error {
@@ -9,6 +10,9 @@ pub enum Error {
     Http(http::Error),
     Request(Box<dyn StdError + Send + Sync>),
     InvalidLocationHeader(String),
+    RedirectPolicy(Uri),
+    Timeout,
+    BodyTooLarge { limit: usize },
 }
 
 impl Error {
@@ -36,6 +40,11 @@ impl fmt::Display for Error {
             Error::Http(ref e) => write!(f, "HTTP error: {}", e),
             Error::Request(ref e) => write!(f, "request error: {}", e),
             Error::InvalidLocationHeader(ref l) => write!(f, "invalid `Location` header: {}", l),
+            Error::RedirectPolicy(ref uri) => write!(f, "redirect to `{}` rejected by policy", uri),
+            Error::Timeout => write!(f, "timed out following redirects"),
+            Error::BodyTooLarge { limit } => {
+                write!(f, "request body exceeds the buffer limit of {} bytes", limit)
+            }
         }
     }
 }
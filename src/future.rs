@@ -2,30 +2,55 @@ use std::error::Error as StdError;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{self, Poll};
+use std::time::Duration;
 
 use bytes::Bytes;
 use hyper::body::{Body, HttpBody};
 use hyper::client::connect::Connect;
 use hyper::{self, Request, Response};
+use tokio::time::{sleep, Sleep};
 
 use crate::buffer::Buffer;
+use crate::cookie::CookieStore;
 use crate::error::Error;
 use crate::machine::{StateMachine, StateMachineDecision};
+use crate::policy::SharedPolicy;
 
-pub(crate) enum FutureInner<C, B> {
-    Lazy(hyper::Client<C, B>, Request<B>, usize),
+enum State<C, B> {
+    Lazy(hyper::Client<C, B>, Request<B>, Config),
     Buffering(hyper::Client<C, B>, StateMachine, Buffer<B>),
     Requesting(hyper::Client<C, B>, StateMachine, hyper::client::ResponseFuture),
     Swapping,
 }
 
+/// The per-request configuration threaded through the state machine.
+pub(crate) struct Config {
+    pub max_redirects: usize,
+    pub policy: SharedPolicy,
+    pub max_body_size: usize,
+    pub cookie_store: Option<CookieStore>,
+}
+
+pub(crate) struct FutureInner<C, B> {
+    state: State<C, B>,
+    timeout: Option<Duration>,
+    // Lazily created on the first poll so the timer is registered with the
+    // runtime that drives the future.
+    deadline: Option<Pin<Box<Sleep>>>,
+}
+
 impl<C, B> FutureInner<C, B> {
     pub fn new(
         client: hyper::Client<C, B>,
         req: Request<B>,
-        max_redirects: usize,
+        config: Config,
+        timeout: Option<Duration>,
     ) -> FutureInner<C, B> {
-        FutureInner::Lazy(client, req, max_redirects)
+        FutureInner {
+            state: State::Lazy(client, req, config),
+            timeout,
+            deadline: None,
+        }
     }
 }
 
@@ -40,31 +65,35 @@ where
 
     fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
         let self_ref = self.get_mut();
-        match ::std::mem::replace(self_ref, FutureInner::Swapping) {
-            FutureInner::Lazy(client, req, max_redirects) => {
-                self_ref.buffer(client, req, max_redirects, cx)
+        if let Some(timeout) = self_ref.timeout {
+            let deadline = self_ref.deadline.get_or_insert_with(|| Box::pin(sleep(timeout)));
+            if Future::poll(deadline.as_mut(), cx).is_ready() {
+                return Poll::Ready(Err(Error::Timeout));
             }
-            FutureInner::Buffering(client, mut state, mut buffer) => {
+        }
+        match ::std::mem::replace(&mut self_ref.state, State::Swapping) {
+            State::Lazy(client, req, config) => self_ref.buffer(client, req, config, cx),
+            State::Buffering(client, mut state, mut buffer) => {
                 match Future::poll(Pin::new(&mut buffer), cx) {
                     Poll::Ready(Ok(body)) => {
                         state.set_body(body);
                         self_ref.request(client, state, cx)
                     }
                     Poll::Ready(Err(e)) => {
-                        return Poll::Ready(Err(Error::request(e)));
+                        return Poll::Ready(Err(e));
                     }
                     Poll::Pending => {
-                        *self_ref = FutureInner::Buffering(client, state, buffer);
+                        self_ref.state = State::Buffering(client, state, buffer);
                         Poll::Pending
                     }
                 }
             }
-            FutureInner::Requesting(client, state, mut future) => {
+            State::Requesting(client, state, mut future) => {
                 match Future::poll(Pin::new(&mut future), cx) {
                     Poll::Ready(Ok(response)) => self_ref.redirect(client, state, response, cx),
                     Poll::Ready(Err(e)) => return Poll::Ready(Err(e.into())),
                     Poll::Pending => {
-                        *self_ref = FutureInner::Requesting(client, state, future);
+                        self_ref.state = State::Requesting(client, state, future);
                         Poll::Pending
                     }
                 }
@@ -85,12 +114,13 @@ where
         &mut self,
         client: hyper::Client<C, B>,
         mut req: Request<B>,
-        max_redirects: usize,
+        config: Config,
         cx: &mut task::Context<'_>,
     ) -> Poll<Result<Response<Body>, Error>> {
-        let state = StateMachine::new(&mut req, max_redirects);
-        let buffer = Buffer::from(req);
-        *self = FutureInner::Buffering(client, state, buffer);
+        let state =
+            StateMachine::new(&mut req, config.max_redirects, config.policy, config.cookie_store);
+        let buffer = Buffer::new(req, config.max_body_size);
+        self.state = State::Buffering(client, state, buffer);
         Future::poll(Pin::new(self), cx)
     }
 
@@ -103,7 +133,7 @@ where
         match state.create_request() {
             Ok(req) => {
                 let future = client.request(req);
-                *self = FutureInner::Requesting(client, state, future);
+                self.state = State::Requesting(client, state, future);
                 Future::poll(Pin::new(self), cx)
             }
             Err(e) => Poll::Ready(Err(Error::request(e))),
@@ -119,7 +149,11 @@ where
     ) -> Poll<Result<Response<Body>, Error>> {
         match state.handle_response(&res)? {
             StateMachineDecision::Continue => self.request(client, state, cx),
-            StateMachineDecision::Return => Poll::Ready(Ok(res)),
+            StateMachineDecision::Return => {
+                let mut res = res;
+                res.extensions_mut().insert(state.history());
+                Poll::Ready(Ok(res))
+            }
         }
     }
 }
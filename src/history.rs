@@ -0,0 +1,32 @@
+
+use hyper::Uri;
+
+/// The record of urls visited while following a chain of redirects.
+///
+/// After a request completes, an instance of this type is attached to the
+/// returned response as a [typed extension](hyper::http::Extensions). Retrieve it
+/// with `response.extensions().get::<RedirectHistory>()` to learn the final
+/// effective url, or to audit every intermediate location that was followed.
+#[derive(Debug, Clone)]
+pub struct RedirectHistory {
+    chain: Vec<Uri>,
+}
+
+impl RedirectHistory {
+    pub(crate) fn new(chain: Vec<Uri>) -> RedirectHistory {
+        RedirectHistory { chain }
+    }
+
+    /// The final url the response was actually served from.
+    ///
+    /// When no redirect was followed, this is the url of the original request.
+    pub fn final_uri(&self) -> &Uri {
+        self.chain.last().expect("redirect chain always contains the original url")
+    }
+
+    /// The ordered list of urls visited, starting with the original request url
+    /// and ending with [`final_uri`](RedirectHistory::final_uri).
+    pub fn chain(&self) -> &[Uri] {
+        &self.chain
+    }
+}
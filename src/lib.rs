@@ -43,6 +43,9 @@
 //!
 //! Redirects to the same host and port, but different paths will retain session information.
 //!
+//! The same headers are also stripped when a redirect downgrades the scheme from `https` to
+//! `http`, so that credentials are never leaked over a cleartext connection.
+//!
 //! # Example
 //!
 //! ```rust
@@ -69,6 +72,9 @@ use std::future::Future;
 use std::pin::Pin;
 use std::task::{self, Poll};
 
+use std::sync::Arc;
+use std::time::Duration;
+
 use bytes::Bytes;
 use hyper::body::HttpBody;
 use hyper::client::connect::Connect;
@@ -76,17 +82,28 @@ use hyper::service::Service;
 use hyper::{Body, Request, Response, Uri};
 
 mod buffer;
+mod cookie;
 mod error;
 mod future;
+mod history;
 mod machine;
+mod policy;
 mod uri;
 
+pub use crate::cookie::CookieStore;
+pub use crate::history::RedirectHistory;
+pub use crate::policy::{Action, Attempt, RedirectPolicy};
+
 use crate::error::Error;
-use crate::future::FutureInner;
+use crate::future::{Config, FutureInner};
+use crate::policy::{FollowAll, SharedPolicy};
 
 /// The default limit on number of redirects to follow.
 pub const DEFAULT_MAX_REDIRECTS: usize = 10;
 
+/// The default limit on the in-memory request body buffer, in bytes (64 MiB).
+pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
 /// Extension trait for adding follow-redirect features to `hyper::Client`.
 pub trait ClientExt<C, B> {
     /// Wrap the `hyper::Client` in a new client that follows redirects.
@@ -106,6 +123,10 @@ impl<C: Clone, B> ClientExt<C, B> for hyper::Client<C, B> {
         Client {
             inner: self.clone(),
             max_redirects: DEFAULT_MAX_REDIRECTS,
+            policy: Arc::new(FollowAll),
+            timeout: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            cookie_store: None,
         }
     }
 }
@@ -117,6 +138,10 @@ impl<C: Clone, B> ClientExt<C, B> for hyper::Client<C, B> {
 pub struct Client<C, B> {
     inner: hyper::Client<C, B>,
     max_redirects: usize,
+    policy: SharedPolicy,
+    timeout: Option<Duration>,
+    max_body_size: usize,
+    cookie_store: Option<CookieStore>,
 }
 
 impl<C: Clone, B> Clone for Client<C, B> {
@@ -124,6 +149,10 @@ impl<C: Clone, B> Clone for Client<C, B> {
         Client {
             inner: self.inner.clone(),
             max_redirects: self.max_redirects,
+            policy: self.policy.clone(),
+            timeout: self.timeout,
+            max_body_size: self.max_body_size,
+            cookie_store: self.cookie_store.clone(),
         }
     }
 }
@@ -158,7 +187,13 @@ where
 {
     /// Send a constructed Request using this client.
     pub fn request(&self, req: Request<B>) -> ResponseFuture {
-        ResponseFuture(Box::pin(FutureInner::new(self.inner.clone(), req, self.max_redirects)))
+        let config = Config {
+            max_redirects: self.max_redirects,
+            policy: self.policy.clone(),
+            max_body_size: self.max_body_size,
+            cookie_store: self.cookie_store.clone(),
+        };
+        ResponseFuture(Box::pin(FutureInner::new(self.inner.clone(), req, config, self.timeout)))
     }
 }
 
@@ -174,6 +209,53 @@ impl<C, B> Client<C, B> {
     pub fn set_max_redirects(&mut self, max_redirects: usize) {
         self.max_redirects = max_redirects;
     }
+
+    /// Set the policy consulted for every redirect before it is followed.
+    ///
+    /// The policy is checked after the next url has been resolved from the
+    /// `Location` header, and can choose to follow the redirect, stop and return
+    /// the current response, or abort with an error. By default every redirect is
+    /// followed, up to the configured redirect limit.
+    pub fn set_redirect_policy<P>(&mut self, policy: P)
+    where
+        P: RedirectPolicy + Send + Sync + 'static,
+    {
+        self.policy = Arc::new(policy);
+    }
+
+    /// Set a deadline bounding the entire operation, including body buffering and
+    /// every redirect hop.
+    ///
+    /// Unlike a per-request timeout, this guarantees a single upper bound across
+    /// the whole redirect chain: if the deadline elapses while buffering the body
+    /// or waiting on any hop, the request resolves with an `Error::Timeout`. By
+    /// default no deadline is applied.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Set the maximum number of bytes buffered in memory from the request body.
+    ///
+    /// The body has to be buffered so it can be replayed when following a
+    /// redirect. A streaming body with no upper bound could otherwise exhaust
+    /// memory before the first request is even made; when the buffer would grow
+    /// past this limit the request fails with `Error::BodyTooLarge`. Defaults to
+    /// [`DEFAULT_MAX_BODY_SIZE`].
+    pub fn set_max_body_size(&mut self, max_body_size: usize) {
+        self.max_body_size = max_body_size;
+    }
+
+    /// Attach a [`CookieStore`] that replays `Set-Cookie` responses across hops.
+    ///
+    /// With a store attached, cookies set by intermediate responses are honored
+    /// and re-sent on subsequent hops they are scoped to, so login and SSO flows
+    /// that set a session cookie and then redirect work as expected. Passing a
+    /// shared jar lets cookies carry over between separate requests. By default
+    /// no store is attached and `Set-Cookie` responses are ignored.
+    pub fn with_cookie_store(mut self, jar: CookieStore) -> Client<C, B> {
+        self.cookie_store = Some(jar);
+        self
+    }
 }
 
 impl<C, B> Service<Request<B>> for Client<C, B>
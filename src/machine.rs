@@ -1,10 +1,13 @@
 
 use bytes::Bytes;
 use hyper::body::Body;
-use hyper::header::HeaderMap;
+use hyper::header::{HeaderMap, HeaderValue, COOKIE};
 use hyper::{self, Method, Request, Response, StatusCode, Uri, Version};
 
+use crate::cookie::CookieStore;
 use crate::error::Error;
+use crate::history::RedirectHistory;
+use crate::policy::{Action, Attempt, SharedPolicy};
 use crate::uri::UriExt;
 
 pub(crate) struct StateMachine {
@@ -15,6 +18,15 @@ pub(crate) struct StateMachine {
     // This is None while we are still receiving the request body.
     pub(crate) request_body: Option<Bytes>,
     remaining_redirects: usize,
+    policy: SharedPolicy,
+    // The number of redirects already followed.
+    hops: usize,
+    // The ordered list of urls visited, starting with the original request url.
+    chain: Vec<Uri>,
+    cookie_store: Option<CookieStore>,
+    // The `Cookie` header the caller supplied on the original request, captured
+    // once so store cookies are merged against a fixed base on every hop.
+    caller_cookie: Option<HeaderValue>,
 }
 
 pub(crate) enum StateMachineDecision {
@@ -23,7 +35,12 @@ pub(crate) enum StateMachineDecision {
 }
 
 impl StateMachine {
-    pub fn new<B>(req: &mut Request<B>, max_redirects: usize) -> StateMachine {
+    pub fn new<B>(
+        req: &mut Request<B>,
+        max_redirects: usize,
+        policy: SharedPolicy,
+        cookie_store: Option<CookieStore>,
+    ) -> StateMachine {
         let mut state = StateMachine {
             method: req.method().clone(),
             uri: req.uri().clone(),
@@ -31,11 +48,50 @@ impl StateMachine {
             headers: HeaderMap::new(),
             request_body: None,
             remaining_redirects: max_redirects,
+            policy,
+            hops: 0,
+            chain: vec![req.uri().clone()],
+            cookie_store,
+            caller_cookie: None,
         };
         state.headers = ::std::mem::replace(req.headers_mut(), HeaderMap::new());
+        state.caller_cookie = state.headers.get(COOKIE).cloned();
+        state.inject_cookies();
         state
     }
 
+    // Rebuild the `Cookie` header from the caller-supplied value (captured once)
+    // merged with the store cookies matching the current url. Rebuilding from the
+    // fixed base avoids accumulating duplicate store cookies across hops.
+    fn inject_cookies(&mut self) {
+        let store = match &self.cookie_store {
+            Some(store) => store,
+            None => return,
+        };
+        let from_store = store.cookie_header(&self.uri);
+        let merged = match (&self.caller_cookie, from_store) {
+            (Some(caller), Some(from_store)) => {
+                let combined =
+                    format!("{}; {}", caller.to_str().unwrap_or(""), from_store.to_str().unwrap_or(""));
+                HeaderValue::from_str(&combined).ok().or(Some(from_store))
+            }
+            (Some(caller), None) => Some(caller.clone()),
+            (None, from_store) => from_store,
+        };
+        match merged {
+            Some(value) => {
+                self.headers.insert(COOKIE, value);
+            }
+            None => {
+                self.headers.remove(COOKIE);
+            }
+        }
+    }
+
+    pub fn history(&self) -> RedirectHistory {
+        RedirectHistory::new(self.chain.clone())
+    }
+
     pub fn set_body(&mut self, body: Bytes) {
         self.request_body = Some(body);
     }
@@ -49,6 +105,9 @@ impl StateMachine {
     }
 
     pub fn handle_response(&mut self, res: &Response<Body>) -> Result<StateMachineDecision, Error> {
+        if let Some(store) = &self.cookie_store {
+            store.store_response(&self.uri, res.headers());
+        }
         match res.status() {
             StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT => {
                 self.follow_redirect(res)
@@ -70,8 +129,22 @@ impl StateMachine {
         self.remaining_redirects -= 1;
         if let Some(location) = res.headers().get(hyper::header::LOCATION) {
             let next = self.uri.compute_redirect(location)?;
+            let attempt = Attempt::new(&self.uri, &next, res.status(), self.hops);
+            match self.policy.check(attempt) {
+                Action::Follow => {}
+                Action::Stop => return Ok(StateMachineDecision::Return),
+                Action::Error => return Err(Error::RedirectPolicy(next)),
+            }
             remove_sensitive_headers(&mut self.headers, &next, &self.uri);
+            // If the hop stripped the `Cookie` header, the caller's cookies no
+            // longer apply and must not be re-injected on the new origin.
+            if !self.headers.contains_key(COOKIE) {
+                self.caller_cookie = None;
+            }
             self.uri = next;
+            self.hops += 1;
+            self.chain.push(self.uri.clone());
+            self.inject_cookies();
             Ok(StateMachineDecision::Continue)
         } else {
             Ok(StateMachineDecision::Return)
@@ -80,7 +153,7 @@ impl StateMachine {
 }
 
 pub fn remove_sensitive_headers(headers: &mut HeaderMap, next: &Uri, previous: &Uri) {
-    if !next.is_same_host(previous) {
+    if !next.is_same_host(previous) || previous.is_downgrade(next) {
         headers.remove("authorization");
         headers.remove("cookie");
         headers.remove("cookie2");
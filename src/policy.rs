@@ -0,0 +1,86 @@
+
+use std::fmt;
+use std::sync::Arc;
+
+use hyper::{StatusCode, Uri};
+
+/// A shared, thread-safe handle to a [`RedirectPolicy`] trait object.
+pub(crate) type SharedPolicy = Arc<dyn RedirectPolicy + Send + Sync>;
+
+/// A single redirect that the client is about to follow.
+///
+/// An `Attempt` is handed to [`RedirectPolicy::check`] after the next url has
+/// been resolved from the `Location` header, but before the request is made.
+/// It exposes enough context to implement domain allowlists, block cross-scheme
+/// hops, or cap the chain dynamically.
+#[derive(Debug)]
+pub struct Attempt<'a> {
+    previous: &'a Uri,
+    next: &'a Uri,
+    status: StatusCode,
+    hops: usize,
+}
+
+impl<'a> Attempt<'a> {
+    pub(crate) fn new(previous: &'a Uri, next: &'a Uri, status: StatusCode, hops: usize) -> Attempt<'a> {
+        Attempt { previous, next, status, hops }
+    }
+
+    /// The url the current response was received from.
+    pub fn previous(&self) -> &Uri {
+        self.previous
+    }
+
+    /// The candidate url the client would follow the redirect to.
+    pub fn next(&self) -> &Uri {
+        self.next
+    }
+
+    /// The status code of the response triggering the redirect.
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The number of redirects already followed before this one.
+    pub fn hops(&self) -> usize {
+        self.hops
+    }
+}
+
+/// The decision a [`RedirectPolicy`] makes about a single redirect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Follow the redirect to the next url.
+    Follow,
+    /// Stop following redirects and return the current response to the caller.
+    Stop,
+    /// Abort the request and resolve with an error.
+    Error,
+}
+
+/// A user-supplied strategy for deciding whether to follow a redirect.
+///
+/// The policy is consulted for every redirect, after the next url has been
+/// computed from the `Location` header. This mirrors the configurable redirect
+/// handling exposed by mainstream clients, and lets callers implement their own
+/// rules on top of the built-in redirect limit.
+pub trait RedirectPolicy {
+    /// Decide what to do with the given redirect [`Attempt`].
+    fn check(&self, attempt: Attempt<'_>) -> Action;
+}
+
+impl fmt::Debug for dyn RedirectPolicy + Send + Sync {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("RedirectPolicy")
+    }
+}
+
+/// The default policy, which follows every redirect up to the client's limit.
+#[derive(Debug)]
+pub(crate) struct FollowAll;
+
+impl RedirectPolicy for FollowAll {
+    fn check(&self, _attempt: Attempt<'_>) -> Action {
+        Action::Follow
+    }
+}
@@ -10,6 +10,9 @@ pub(crate) trait UriExt {
     fn compute_redirect(&self, location: &HeaderValue) -> Result<Uri, Error>;
     /// Check whether this [Uri] and the [other] share the same host and port.
     fn is_same_host(&self, other: &Uri) -> bool;
+    /// Check whether following a redirect from this [Uri] to [next] downgrades
+    /// the scheme from `https` to `http`.
+    fn is_downgrade(&self, next: &Uri) -> bool;
 }
 
 impl UriExt for Uri {
@@ -36,6 +39,10 @@ impl UriExt for Uri {
     fn is_same_host(&self, other: &Uri) -> bool {
         self.host() == other.host() && self.port() == other.port()
     }
+
+    fn is_downgrade(&self, next: &Uri) -> bool {
+        self.scheme_str() == Some("https") && next.scheme_str() == Some("http")
+    }
 }
 
 #[cfg(test)]
@@ -65,4 +72,13 @@ mod tests {
         let new = base.compute_redirect(&location).unwrap();
         assert_eq!("https://example.com/bar?y=1", &new.to_string());
     }
+
+    #[test]
+    fn detects_scheme_downgrade() {
+        let secure = "https://example.org/a".parse::<Uri>().unwrap();
+        let cleartext = "http://example.org/b".parse::<Uri>().unwrap();
+        assert!(secure.is_downgrade(&cleartext));
+        assert!(!cleartext.is_downgrade(&secure));
+        assert!(!secure.is_downgrade(&"https://example.org/b".parse::<Uri>().unwrap()));
+    }
 }